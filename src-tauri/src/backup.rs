@@ -0,0 +1,242 @@
+// Copyright (C) 2026  Галимзянов Г.Р.
+//
+// This file is part of time-to-table
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Версионные резервные копии, создаваемые перед перезаписью существующего файла,
+//! чтобы пользователь мог откатиться к предыдущей версии таблицы в один клик.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Формирует путь к резервной копии вида `name.2026-01-01T12-00-00.ext`
+/// рядом с оригинальным файлом
+fn backup_path_for(original: &Path, timestamp: &str) -> PathBuf {
+    let dir = original.parent().unwrap_or_else(|| Path::new("."));
+    let stem = original
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = original.extension().map(|e| e.to_string_lossy().to_string());
+
+    let file_name = match ext {
+        Some(ext) => format!("{stem}.{timestamp}.{ext}"),
+        None => format!("{stem}.{timestamp}"),
+    };
+
+    dir.join(file_name)
+}
+
+/// Форматирует момент времени как `YYYY-MM-DDTHH-MM-SS` (UTC), без внешних зависимостей
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+    let days = secs / 86_400;
+    let time_of_day = secs % 86_400;
+    let (hour, minute, second) = (time_of_day / 3600, (time_of_day / 60) % 60, time_of_day % 60);
+
+    // Дни от эпохи в гражданскую дату — алгоритм civil_from_days (Howard Hinnant)
+    let z = days as i64 + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = (z - era * 146_097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if month <= 2 { y + 1 } else { y };
+
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}-{minute:02}-{second:02}")
+}
+
+/// Если по целевому пути уже есть файл, копирует его в версионированную резервную
+/// копию в той же директории. Возвращает путь к созданной копии, либо `None`,
+/// если оригинала ещё не существует. Сгенерированный путь копии сам должен
+/// пройти `is_allowed`, как и любой другой путь, с которым работает приложение
+pub fn rotate_backup(original: &Path, is_allowed: impl Fn(&Path) -> bool) -> Result<Option<PathBuf>, String> {
+    if !original.exists() {
+        return Ok(None);
+    }
+
+    let timestamp = format_timestamp(SystemTime::now());
+    let mut backup = backup_path_for(original, &timestamp);
+
+    // Несколько сохранений в пределах одной секунды дают одинаковый таймстамп —
+    // подбираем свободное имя, чтобы не перезаписать уже существующую копию
+    let mut suffix = 1;
+    while backup.exists() {
+        backup = backup_path_for(original, &format!("{timestamp}-{suffix}"));
+        suffix += 1;
+    }
+
+    if !is_allowed(&backup) {
+        return Err("Резервная копия выходит за пределы разрешённой области".into());
+    }
+
+    fs::copy(original, &backup).map_err(|e| format!("Ошибка создания резервной копии: {}", e))?;
+
+    Ok(Some(backup))
+}
+
+/// Проверяет, что строка — таймстамп вида `YYYY-MM-DDTHH-MM-SS`, как их
+/// производит [`format_timestamp`], с необязательным суффиксом `-N`,
+/// добавляемым [`rotate_backup`] при коллизии в пределах одной секунды
+fn is_backup_timestamp(candidate: &str) -> bool {
+    if candidate.len() < 19 || !candidate.is_char_boundary(19) {
+        return false;
+    }
+
+    let core = &candidate[..19];
+    let core_ok = core.bytes().enumerate().all(|(i, b)| match i {
+        4 | 7 | 13 | 16 => b == b'-',
+        10 => b == b'T',
+        _ => b.is_ascii_digit(),
+    });
+    if !core_ok {
+        return false;
+    }
+
+    let suffix = &candidate[19..];
+    suffix.is_empty() || (suffix.starts_with('-') && suffix[1..].bytes().all(|b| b.is_ascii_digit()) && suffix.len() > 1)
+}
+
+/// Возвращает резервные копии для файла, отсортированные от новых к старым.
+/// В отличие от простого сопоставления по имени и расширению, средний
+/// компонент имени обязан быть таймстампом, который сама же [`rotate_backup`]
+/// и проставляет — иначе случайный `report.final.xlsx` выглядел бы как
+/// резервная копия `report.xlsx` и был бы предложен для восстановления
+pub fn list_backups(original: &Path) -> Result<Vec<PathBuf>, String> {
+    let dir = original
+        .parent()
+        .ok_or_else(|| "У пути нет родительской директории".to_string())?;
+    let stem = original
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = original.extension().map(|e| e.to_string_lossy().to_string());
+    let prefix = format!("{stem}.");
+
+    let read_dir = fs::read_dir(dir).map_err(|e| format!("Ошибка чтения директории: {}", e))?;
+
+    let mut backups: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                return false;
+            };
+            let Some(rest) = name.strip_prefix(&prefix) else {
+                return false;
+            };
+
+            let timestamp = match &ext {
+                Some(ext) => match rest.strip_suffix(&format!(".{ext}")) {
+                    Some(timestamp) => timestamp,
+                    None => return false,
+                },
+                None => rest,
+            };
+
+            is_backup_timestamp(timestamp)
+        })
+        .collect();
+
+    // Сортируем по таймстампу и числовому суффиксу коллизии, а не по имени целиком:
+    // при сравнении имён `...-00-1.xlsx` оказалось бы раньше `...-00.xlsx`
+    let ext_len = ext.as_ref().map_or(0, |ext| ext.len() + 1);
+    backups.sort_by_cached_key(|path| {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let timestamp = &name[prefix.len()..name.len() - ext_len];
+        let suffix = timestamp[19..]
+            .strip_prefix('-')
+            .and_then(|n| n.parse::<u64>().ok())
+            .unwrap_or(0);
+        std::cmp::Reverse((timestamp[..19].to_string(), suffix))
+    });
+
+    Ok(backups)
+}
+
+/// Восстанавливает файл из указанной резервной копии, перезаписывая оригинал
+pub fn restore_backup(original: &Path, backup: &Path) -> Result<(), String> {
+    fs::copy(backup, original).map_err(|e| format!("Ошибка восстановления из резервной копии: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_path_for_keeps_extension() {
+        let original = Path::new("/tmp/report.xlsx");
+        let backup = backup_path_for(original, "2026-01-01T12-00-00");
+        assert_eq!(backup, Path::new("/tmp/report.2026-01-01T12-00-00.xlsx"));
+    }
+
+    #[test]
+    fn backup_path_for_without_extension() {
+        let original = Path::new("/tmp/report");
+        let backup = backup_path_for(original, "2026-01-01T12-00-00");
+        assert_eq!(backup, Path::new("/tmp/report.2026-01-01T12-00-00"));
+    }
+
+    #[test]
+    fn format_timestamp_matches_known_epoch_seconds() {
+        // 2026-01-01T12-00-00 UTC
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_767_268_800);
+        assert_eq!(format_timestamp(time), "2026-01-01T12-00-00");
+    }
+
+    #[test]
+    fn is_backup_timestamp_accepts_plain_and_suffixed() {
+        assert!(is_backup_timestamp("2026-01-01T12-00-00"));
+        assert!(is_backup_timestamp("2026-01-01T12-00-00-1"));
+        assert!(is_backup_timestamp("2026-01-01T12-00-00-12"));
+    }
+
+    #[test]
+    fn is_backup_timestamp_rejects_non_timestamps() {
+        assert!(!is_backup_timestamp("final"));
+        assert!(!is_backup_timestamp("v2"));
+        assert!(!is_backup_timestamp("2026-01-01T12-00-00-")); // пустой суффикс после дефиса
+        assert!(!is_backup_timestamp("2026-01-01T12:00:00")); // двоеточия вместо дефисов
+        assert!(!is_backup_timestamp("2026-01-01"));
+    }
+
+    #[test]
+    fn list_backups_ignores_siblings_that_are_not_timestamped_backups() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let original = dir.path().join("report.xlsx");
+        fs::write(&original, b"current").unwrap();
+
+        let real_backup = dir.path().join("report.2026-01-01T12-00-00.xlsx");
+        fs::write(&real_backup, b"old version").unwrap();
+
+        // Похожие по имени, но не резервные копии — не должны попасть в список
+        fs::write(dir.path().join("report.final.xlsx"), b"unrelated").unwrap();
+        fs::write(dir.path().join("report.v2.xlsx"), b"unrelated").unwrap();
+
+        let backups = list_backups(&original).expect("list_backups");
+
+        assert_eq!(backups, vec![real_backup]);
+    }
+
+    #[test]
+    fn rotate_backup_creates_a_listable_copy_and_rejects_disallowed_paths() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let original = dir.path().join("report.xlsx");
+        fs::write(&original, b"current").unwrap();
+
+        let created = rotate_backup(&original, |_| true)
+            .expect("rotate_backup should succeed")
+            .expect("original exists, so a backup path is returned");
+        assert!(created.exists());
+        assert_eq!(list_backups(&original).unwrap(), vec![created]);
+
+        let err = rotate_backup(&original, |_| false).expect_err("backup path rejected by scope");
+        assert!(err.contains("разрешённой области"));
+    }
+}
@@ -6,84 +6,249 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
 
+mod backup;
+mod scope;
+
+use std::io::Write;
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Проверяет что путь находится в разрешённой директории
-fn is_path_allowed(path: &PathBuf) -> bool {
-    let allowed_dirs: Vec<PathBuf> = [
-        dirs::download_dir(),
-        dirs::document_dir(),
-        dirs::desktop_dir(),
-    ]
-    .into_iter()
-    .flatten()
-    .collect();
-
-    // Канонизируем путь для защиты от ../ атак
-    let canonical = match path.canonicalize() {
-        Ok(p) => p,
-        Err(_) => {
-            // Если файл ещё не существует, проверяем родительскую директорию
-            if let Some(parent) = path.parent() {
-                match parent.canonicalize() {
-                    Ok(p) => p,
-                    Err(_) => return false,
-                }
-            } else {
-                return false;
-            }
-        }
-    };
+use base64::Engine;
+use scope::ScopeState;
+use tauri::{AppHandle, Manager, State};
 
-    allowed_dirs.iter().any(|dir| {
-        if let Ok(canonical_dir) = dir.canonicalize() {
-            canonical.starts_with(&canonical_dir)
-        } else {
-            false
-        }
-    })
+/// Пишет содержимое во временный файл рядом с `path` и только после полного
+/// сброса на диск атомарно переименовывает его поверх цели, чтобы прерванная
+/// запись (сбой, отключение питания) не оставила повреждённый наполовину
+/// записанный файл. Используется и текстовым, и бинарным путём сохранения.
+fn write_atomically(path: &std::path::Path, bytes: &[u8]) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| "У пути сохранения нет родительской директории".to_string())?;
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| format!("Ошибка создания временного файла: {}", e))?;
+
+    // Временный файл создаётся с урезанными правами доступа — переносим права
+    // существующего файла, чтобы перезапись не ужесточала их исподтишка
+    #[cfg(unix)]
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = temp_file.as_file().set_permissions(metadata.permissions());
+    }
+
+    temp_file
+        .write_all(bytes)
+        .and_then(|_| temp_file.flush())
+        .and_then(|_| temp_file.as_file().sync_all())
+        .map_err(|e| format!("Ошибка записи: {}", e))?;
+
+    temp_file
+        .persist(path)
+        .map_err(|e| format!("Ошибка переименования временного файла: {}", e.error))?;
+
+    Ok(())
 }
 
 /// Безопасная запись файла с проверкой пути
+///
+/// Если по целевому пути уже есть файл, перед записью он версионируется в
+/// резервную копию (см. [`backup::rotate_backup`]), чтобы пользователь мог
+/// откатиться к предыдущей версии. Запись выполняется атомарно через
+/// [`write_atomically`].
 #[tauri::command]
-fn save_file_secure(path: String, content: String) -> Result<String, String> {
+fn save_file_secure(state: State<ScopeState>, path: String, content: String) -> Result<String, String> {
     let path_buf = PathBuf::from(&path);
-    
-    if !is_path_allowed(&path_buf) {
-        return Err("Сохранение разрешено только в папки: Загрузки, Документы или Рабочий стол".into());
+
+    if !scope::is_path_allowed(&state, &path_buf) {
+        return Err("Сохранение разрешено только в разрешённые области".into());
     }
-    
-    std::fs::write(&path_buf, &content)
-        .map_err(|e| format!("Ошибка записи: {}", e))?;
-    
+
+    backup::rotate_backup(&path_buf, |p| scope::is_path_allowed(&state, p))?;
+    write_atomically(&path_buf, content.as_bytes())?;
+
     Ok(path)
 }
 
 /// Безопасное чтение файла с проверкой пути
 #[tauri::command]
-fn read_file_secure(path: String) -> Result<String, String> {
+fn read_file_secure(state: State<ScopeState>, path: String) -> Result<String, String> {
     let path_buf = PathBuf::from(&path);
-    
-    if !is_path_allowed(&path_buf) {
-        return Err("Чтение разрешено только из папок: Загрузки, Документы или Рабочий стол".into());
+
+    if !scope::is_path_allowed(&state, &path_buf) {
+        return Err("Чтение разрешено только из разрешённых областей".into());
     }
-    
+
     std::fs::read_to_string(&path_buf)
         .map_err(|e| format!("Ошибка чтения: {}", e))
 }
 
-/// Возвращает список разрешённых директорий
+/// Безопасная запись бинарного файла (содержимое передаётся в base64)
+/// с проверкой пути — для экспорта xlsx/ods/pdf и прочих форматов,
+/// которые не являются валидным UTF-8. Как и [`save_file_secure`], версионирует
+/// существующий файл через [`backup::rotate_backup`] и пишет атомарно через
+/// [`write_atomically`].
+#[tauri::command]
+fn save_file_binary(state: State<ScopeState>, path: String, content_base64: String) -> Result<String, String> {
+    let path_buf = PathBuf::from(&path);
+
+    if !scope::is_path_allowed(&state, &path_buf) {
+        return Err("Сохранение разрешено только в разрешённые области".into());
+    }
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&content_base64)
+        .map_err(|e| format!("Ошибка декодирования base64: {}", e))?;
+
+    backup::rotate_backup(&path_buf, |p| scope::is_path_allowed(&state, p))?;
+    write_atomically(&path_buf, &bytes)?;
+
+    Ok(path)
+}
+
+/// Безопасное чтение бинарного файла с проверкой пути, возвращает содержимое в base64
+#[tauri::command]
+fn read_file_binary(state: State<ScopeState>, path: String) -> Result<String, String> {
+    let path_buf = PathBuf::from(&path);
+
+    if !scope::is_path_allowed(&state, &path_buf) {
+        return Err("Чтение разрешено только из разрешённых областей".into());
+    }
+
+    let bytes = std::fs::read(&path_buf).map_err(|e| format!("Ошибка чтения: {}", e))?;
+
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Возвращает список резервных копий файла, доступных для восстановления,
+/// от самой новой к самой старой
 #[tauri::command]
-fn get_allowed_dirs() -> Vec<String> {
-    [
-        dirs::download_dir(),
-        dirs::document_dir(), 
-        dirs::desktop_dir(),
-    ]
-    .into_iter()
-    .flatten()
-    .map(|p| p.to_string_lossy().to_string())
-    .collect()
+fn list_file_backups(state: State<ScopeState>, path: String) -> Result<Vec<String>, String> {
+    let path_buf = PathBuf::from(&path);
+
+    if !scope::is_path_allowed(&state, &path_buf) {
+        return Err("Просмотр резервных копий разрешён только в разрешённых областях".into());
+    }
+
+    Ok(backup::list_backups(&path_buf)?
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Восстанавливает файл из указанной резервной копии, перезаписывая оригинал.
+/// Перед восстановлением текущая версия файла тоже версионируется, чтобы
+/// неудачное восстановление (например, из повреждённой копии) само можно
+/// было откатить
+#[tauri::command]
+fn restore_backup(state: State<ScopeState>, path: String, backup_path: String) -> Result<(), String> {
+    let path_buf = PathBuf::from(&path);
+    let backup_buf = PathBuf::from(&backup_path);
+
+    if !scope::is_path_allowed(&state, &path_buf) || !scope::is_path_allowed(&state, &backup_buf) {
+        return Err("Восстановление разрешено только в разрешённых областях".into());
+    }
+
+    backup::rotate_backup(&path_buf, |p| scope::is_path_allowed(&state, p))?;
+    backup::restore_backup(&path_buf, &backup_buf)
+}
+
+/// Возвращает список базовых директорий текущей разрешённой области
+#[tauri::command]
+fn get_allowed_dirs(state: State<ScopeState>) -> Vec<String> {
+    scope::list_allowed_dirs(&state)
+}
+
+/// Добавляет директорию (с необязательной glob-маской) в разрешённую область
+#[tauri::command]
+fn add_allowed_dir(
+    app: AppHandle,
+    state: State<ScopeState>,
+    path: String,
+    pattern: Option<String>,
+) -> Result<(), String> {
+    scope::add_allowed_dir(&app, &state, PathBuf::from(path), pattern)
+}
+
+/// Убирает директорию из разрешённой области
+#[tauri::command]
+fn remove_allowed_dir(app: AppHandle, state: State<ScopeState>, path: String) -> Result<(), String> {
+    scope::remove_allowed_dir(&app, &state, &PathBuf::from(path))
+}
+
+/// Метаданные элемента директории для файлового браузера
+#[derive(serde::Serialize)]
+struct EntryMetaData {
+    name: String,
+    path: String,
+    size: u64,
+    is_directory: bool,
+    is_file: bool,
+    is_symlink: bool,
+    /// Количество элементов внутри директории, `None` для файлов
+    children_count: Option<usize>,
+    created: Option<u64>,
+    modified: Option<u64>,
+    accessed: Option<u64>,
+}
+
+/// Переводит `SystemTime` в секунды unix-эпохи, если это возможно
+fn system_time_to_unix(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+/// Безопасный листинг директории с проверкой пути
+#[tauri::command]
+fn list_dir_secure(state: State<ScopeState>, path: String) -> Result<Vec<EntryMetaData>, String> {
+    let path_buf = PathBuf::from(&path);
+
+    if !scope::is_path_allowed(&state, &path_buf) {
+        return Err("Просмотр разрешён только в разрешённых областях".into());
+    }
+
+    let read_dir = std::fs::read_dir(&path_buf)
+        .map_err(|e| format!("Ошибка чтения директории: {}", e))?;
+
+    let mut entries = Vec::new();
+    for entry in read_dir {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        let entry_path = entry.path();
+
+        // Резолвим симлинки и проверяем итоговый путь на принадлежность разрешённой области
+        let resolved = entry_path.canonicalize().unwrap_or_else(|_| entry_path.clone());
+        if !scope::is_path_allowed(&state, &resolved) {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        let is_directory = metadata.is_dir();
+        let children_count = if is_directory {
+            std::fs::read_dir(&entry_path).ok().map(|rd| rd.count())
+        } else {
+            None
+        };
+
+        entries.push(EntryMetaData {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path: entry_path.to_string_lossy().to_string(),
+            size: metadata.len(),
+            is_directory,
+            is_file: metadata.is_file(),
+            is_symlink: metadata.file_type().is_symlink(),
+            children_count,
+            created: system_time_to_unix(metadata.created()),
+            modified: system_time_to_unix(metadata.modified()),
+            accessed: system_time_to_unix(metadata.accessed()),
+        });
+    }
+
+    Ok(entries)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -95,14 +260,25 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             save_file_secure,
             read_file_secure,
-            get_allowed_dirs
+            save_file_binary,
+            read_file_binary,
+            list_file_backups,
+            restore_backup,
+            get_allowed_dirs,
+            add_allowed_dir,
+            remove_allowed_dir,
+            list_dir_secure
         ])
-        .setup(|_app| {
+        .setup(|app| {
             // DevTools только в debug режиме
             #[cfg(debug_assertions)]
             {
-                
+
             }
+
+            let entries = scope::init_scope_file(app.handle())?;
+            app.manage(ScopeState(std::sync::Mutex::new(entries)));
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,260 @@
+// Copyright (C) 2026  Галимзянов Г.Р.
+//
+// This file is part of time-to-table
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Область разрешённых директорий (по аналогии со scope allow/deny в Tauri FS plugin).
+//!
+//! Список баз хранится построчно (JSONL) в `scope.jsonl` внутри директории данных
+//! приложения и подгружается в управляемое состояние `ScopeState`, чтобы
+//! `is_path_allowed` не зависела от жёстко заданных системных папок.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const SCOPE_FILE_NAME: &str = "scope.jsonl";
+
+/// Одна строка области: базовая директория и необязательная glob-маска внутри неё
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScopeEntry {
+    pub base: PathBuf,
+    pub pattern: Option<String>,
+}
+
+/// Управляемое состояние со списком разрешённых областей
+pub struct ScopeState(pub Mutex<Vec<ScopeEntry>>);
+
+/// Базовые директории, с которых область инициализируется при первом запуске
+fn default_scope_entries() -> Vec<ScopeEntry> {
+    [dirs::download_dir(), dirs::document_dir(), dirs::desktop_dir()]
+        .into_iter()
+        .flatten()
+        .map(|base| ScopeEntry { base, pattern: None })
+        .collect()
+}
+
+/// Путь к файлу области в директории данных приложения
+pub fn scope_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Не удалось определить директорию данных приложения: {}", e))?;
+    Ok(data_dir.join(SCOPE_FILE_NAME))
+}
+
+/// Читает область из JSONL-файла, по одной записи на строку
+pub fn load_scope(path: &Path) -> Vec<ScopeEntry> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Перезаписывает область на диске, по одной записи на строку
+pub fn save_scope(path: &Path, entries: &[ScopeEntry]) -> Result<(), String> {
+    let mut content = String::new();
+    for entry in entries {
+        let line =
+            serde_json::to_string(entry).map_err(|e| format!("Ошибка сериализации области: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    fs::write(path, content).map_err(|e| format!("Ошибка записи файла области: {}", e))
+}
+
+/// Создаёт файл области с базовыми директориями, если он ещё не существует,
+/// и возвращает загруженный (или только что созданный) список записей
+pub fn init_scope_file(app: &AppHandle) -> Result<Vec<ScopeEntry>, String> {
+    let path = scope_file_path(app)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Ошибка создания директории данных: {}", e))?;
+    }
+
+    if !path.exists() {
+        let defaults = default_scope_entries();
+        save_scope(&path, &defaults)?;
+        return Ok(defaults);
+    }
+
+    Ok(load_scope(&path))
+}
+
+/// Проверяет, что путь подпадает под разрешённую область: находится внутри одной
+/// из баз и, если указана маска, соответствует ей
+pub fn is_path_allowed(state: &ScopeState, path: &Path) -> bool {
+    // Путь, по которому проверяем вхождение в базу и соответствие маске: если
+    // файл уже существует — его канонический путь, иначе — канонический путь
+    // родительской директории с исходным именем файла. Так маска вида
+    // `**/*.xlsx` продолжает матчиться на ещё не созданный при экспорте файл.
+    let target = match path.canonicalize() {
+        Ok(p) => p,
+        Err(_) => {
+            let parent = match path.parent().and_then(|parent| parent.canonicalize().ok()) {
+                Some(p) => p,
+                None => return false,
+            };
+            match path.file_name() {
+                Some(name) => parent.join(name),
+                None => return false,
+            }
+        }
+    };
+
+    let entries = state.0.lock().expect("scope mutex poisoned");
+
+    entries.iter().any(|entry| {
+        let Ok(canonical_base) = entry.base.canonicalize() else {
+            return false;
+        };
+
+        if !target.starts_with(&canonical_base) {
+            return false;
+        }
+
+        match &entry.pattern {
+            // Маска задаётся относительно базы (`*.xlsx`, `**/*.xlsx`), поэтому
+            // матчим не саму маску, а базу с приклеенной маской
+            Some(pattern) => glob::Pattern::new(&canonical_base.join(pattern).to_string_lossy())
+                .map(|p| p.matches_path(&target))
+                .unwrap_or(false),
+            None => true,
+        }
+    })
+}
+
+/// Добавляет новую базовую директорию (с необязательной маской) в область
+/// и сохраняет обновлённый список на диск. Состояние в памяти обновляется
+/// только после успешной записи на диск, чтобы оба никогда не расходились
+pub fn add_allowed_dir(
+    app: &AppHandle,
+    state: &ScopeState,
+    base: PathBuf,
+    pattern: Option<String>,
+) -> Result<(), String> {
+    let path = scope_file_path(app)?;
+    let mut entries = state.0.lock().expect("scope mutex poisoned");
+
+    let mut updated = entries.clone();
+    updated.push(ScopeEntry { base, pattern });
+    save_scope(&path, &updated)?;
+
+    *entries = updated;
+    Ok(())
+}
+
+/// Удаляет базовую директорию из области и сохраняет обновлённый список на диск.
+/// Состояние в памяти обновляется только после успешной записи на диск
+pub fn remove_allowed_dir(app: &AppHandle, state: &ScopeState, base: &Path) -> Result<(), String> {
+    let path = scope_file_path(app)?;
+    let mut entries = state.0.lock().expect("scope mutex poisoned");
+
+    let mut updated = entries.clone();
+    updated.retain(|entry| entry.base != base);
+    save_scope(&path, &updated)?;
+
+    *entries = updated;
+    Ok(())
+}
+
+/// Возвращает текущий список базовых директорий области в виде строк
+pub fn list_allowed_dirs(state: &ScopeState) -> Vec<String> {
+    state
+        .0
+        .lock()
+        .expect("scope mutex poisoned")
+        .iter()
+        .map(|entry| entry.base.to_string_lossy().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with(entries: Vec<ScopeEntry>) -> ScopeState {
+        ScopeState(Mutex::new(entries))
+    }
+
+    #[test]
+    fn allows_existing_file_inside_base() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let file = dir.path().join("report.xlsx");
+        fs::write(&file, b"content").unwrap();
+
+        let state = state_with(vec![ScopeEntry {
+            base: dir.path().to_path_buf(),
+            pattern: None,
+        }]);
+
+        assert!(is_path_allowed(&state, &file));
+    }
+
+    #[test]
+    fn rejects_path_outside_every_base() {
+        let inside = tempfile::tempdir().expect("tempdir");
+        let outside = tempfile::tempdir().expect("tempdir");
+        let file = outside.path().join("report.xlsx");
+        fs::write(&file, b"content").unwrap();
+
+        let state = state_with(vec![ScopeEntry {
+            base: inside.path().to_path_buf(),
+            pattern: None,
+        }]);
+
+        assert!(!is_path_allowed(&state, &file));
+    }
+
+    #[test]
+    fn glob_pattern_matches_not_yet_existing_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let not_yet_created = dir.path().join("export.xlsx");
+
+        let state = state_with(vec![ScopeEntry {
+            base: dir.path().to_path_buf(),
+            pattern: Some("*.xlsx".to_string()),
+        }]);
+
+        // Файл ещё не создан (типичный случай для save_file_secure/save_file_binary),
+        // но маска всё равно должна матчиться на итоговый путь
+        assert!(is_path_allowed(&state, &not_yet_created));
+    }
+
+    #[test]
+    fn glob_pattern_rejects_non_matching_extension() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let not_yet_created = dir.path().join("export.pdf");
+
+        let state = state_with(vec![ScopeEntry {
+            base: dir.path().to_path_buf(),
+            pattern: Some("*.xlsx".to_string()),
+        }]);
+
+        assert!(!is_path_allowed(&state, &not_yet_created));
+    }
+
+    #[test]
+    fn glob_pattern_with_recursive_segment_matches_nested_path() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let nested_dir = dir.path().join("2026");
+        fs::create_dir_all(&nested_dir).unwrap();
+        let not_yet_created = nested_dir.join("export.xlsx");
+
+        let state = state_with(vec![ScopeEntry {
+            base: dir.path().to_path_buf(),
+            pattern: Some("**/*.xlsx".to_string()),
+        }]);
+
+        assert!(is_path_allowed(&state, &not_yet_created));
+    }
+}